@@ -0,0 +1,314 @@
+use std::cell::UnsafeCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// An async-aware interior-mutability cell, modeled on Deno's `AsyncRefCell`.
+///
+/// Unlike `std::sync::RwLock`, a pending borrow doesn't block a thread: `borrow()`
+/// and `borrow_mut()` return futures that resolve once the cell is available,
+/// parking a waker in a FIFO queue in the meantime. That's what lets several async
+/// tasks share one `T` (e.g. a results buffer) without the lending processors'
+/// "cannot borrow more than once" limitation getting in the way.
+///
+/// Always accessed through an [`RcRef`] handle, which clones cheaply so every task
+/// can hold its own reference to the same underlying cell.
+pub struct AsyncRefCell<T> {
+    value: UnsafeCell<T>,
+    state: Mutex<BorrowState>,
+}
+
+struct BorrowState {
+    shared_count: usize,
+    exclusive: bool,
+    wakers: VecDeque<Waiter>,
+}
+
+/// A parked waker, tagged with the kind of borrow it's waiting for.
+///
+/// The tag lets a release wake every contiguous *shared* waiter at once (they
+/// can all proceed together) while stopping at the first queued *exclusive*
+/// waiter, which must wait for the shared borrows ahead of it to finish.
+enum Waiter {
+    Shared(Waker),
+    Exclusive(Waker),
+}
+
+/// Wakes the waiters that can now make progress after an exclusive borrow is
+/// released: every contiguous `Shared` waiter at the front of the queue (they
+/// can all proceed concurrently), or just the front `Exclusive` waiter if
+/// there's no `Shared` waiter ahead of it.
+fn wake_after_exclusive_release(state: &mut BorrowState) {
+    if matches!(state.wakers.front(), Some(Waiter::Exclusive(_))) {
+        wake_front(state);
+        return;
+    }
+    while let Some(Waiter::Shared(_)) = state.wakers.front() {
+        if let Some(Waiter::Shared(waker)) = state.wakers.pop_front() {
+            waker.wake();
+        }
+    }
+}
+
+/// Wakes the single waiter at the front of the queue, if any.
+fn wake_front(state: &mut BorrowState) {
+    match state.wakers.pop_front() {
+        Some(Waiter::Shared(waker)) | Some(Waiter::Exclusive(waker)) => waker.wake(),
+        None => {}
+    }
+}
+
+unsafe impl<T: Send> Send for AsyncRefCell<T> {}
+unsafe impl<T: Send + Sync> Sync for AsyncRefCell<T> {}
+
+impl<T> AsyncRefCell<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            state: Mutex::new(BorrowState {
+                shared_count: 0,
+                exclusive: false,
+                wakers: VecDeque::new(),
+            }),
+        }
+    }
+}
+
+/// A clonable handle to a shared [`AsyncRefCell`], in the spirit of Deno's
+/// `RcRef`. Every clone points at the same cell, so multiple closures or tasks
+/// can each hold their own handle and borrow it independently.
+pub struct RcRef<T>(Arc<AsyncRefCell<T>>);
+
+impl<T> RcRef<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(AsyncRefCell::new(value)))
+    }
+
+    /// Resolves to a shared guard once no exclusive borrow is active.
+    pub fn borrow(&self) -> BorrowFuture<T> {
+        BorrowFuture { cell: self.0.clone() }
+    }
+
+    /// Resolves to an exclusive guard once there are zero outstanding borrows.
+    pub fn borrow_mut(&self) -> BorrowMutFuture<T> {
+        BorrowMutFuture { cell: self.0.clone() }
+    }
+
+    /// Unwraps the cell's value if this is the last handle pointing at it,
+    /// returning the handle back if other clones (or outstanding borrows) remain.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        match Arc::try_unwrap(this.0) {
+            Ok(cell) => Ok(cell.value.into_inner()),
+            Err(arc) => Err(Self(arc)),
+        }
+    }
+}
+
+impl<T> Clone for RcRef<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// Future returned by [`RcRef::borrow`].
+pub struct BorrowFuture<T> {
+    cell: Arc<AsyncRefCell<T>>,
+}
+
+impl<T> Future for BorrowFuture<T> {
+    type Output = Ref<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.cell.state.lock().unwrap();
+        if state.exclusive {
+            state.wakers.push_back(Waiter::Shared(cx.waker().clone()));
+            return Poll::Pending;
+        }
+        state.shared_count += 1;
+        drop(state);
+        Poll::Ready(Ref { cell: self.cell.clone() })
+    }
+}
+
+/// Future returned by [`RcRef::borrow_mut`].
+pub struct BorrowMutFuture<T> {
+    cell: Arc<AsyncRefCell<T>>,
+}
+
+impl<T> Future for BorrowMutFuture<T> {
+    type Output = RefMut<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.cell.state.lock().unwrap();
+        if state.exclusive || state.shared_count > 0 {
+            state.wakers.push_back(Waiter::Exclusive(cx.waker().clone()));
+            return Poll::Pending;
+        }
+        state.exclusive = true;
+        drop(state);
+        Poll::Ready(RefMut { cell: self.cell.clone() })
+    }
+}
+
+/// RAII shared-borrow guard. Dropping it decrements the cell's shared count and,
+/// once it reaches zero, wakes the single waiter queued at the front. That is
+/// usually an exclusive waiter, since shared waiters only queue behind the
+/// exclusive flag, never behind a shared count.
+pub struct Ref<T> {
+    cell: Arc<AsyncRefCell<T>>,
+}
+
+impl<T> Deref for Ref<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for Ref<T> {
+    fn drop(&mut self) {
+        let mut state = self.cell.state.lock().unwrap();
+        state.shared_count -= 1;
+        if state.shared_count == 0 {
+            wake_front(&mut state);
+        }
+    }
+}
+
+/// RAII exclusive-borrow guard. Dropping it clears the cell's exclusive flag and
+/// wakes every contiguous shared waiter queued at the front, since they can all
+/// proceed concurrently once the exclusive borrow is gone; it stops at the first
+/// queued exclusive waiter rather than waking it too. If an exclusive waiter is
+/// at the front of the queue, only that one is woken.
+pub struct RefMut<T> {
+    cell: Arc<AsyncRefCell<T>>,
+}
+
+impl<T> Deref for RefMut<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.cell.value.get() }
+    }
+}
+
+impl<T> DerefMut for RefMut<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.cell.value.get() }
+    }
+}
+
+impl<T> Drop for RefMut<T> {
+    fn drop(&mut self) {
+        let mut state = self.cell.state.lock().unwrap();
+        state.exclusive = false;
+        wake_after_exclusive_release(&mut state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_concurrent_shared_borrows() {
+        let cell = RcRef::new(5);
+
+        let a = cell.borrow().await;
+        let b = cell.borrow().await;
+
+        assert_eq!(*a, 5);
+        assert_eq!(*b, 5);
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_borrow_excludes_shared() {
+        let cell = RcRef::new(vec![1, 2, 3]);
+
+        {
+            let mut guard = cell.borrow_mut().await;
+            guard.push(4);
+            assert!(
+                futures::FutureExt::now_or_never(cell.borrow()).is_none(),
+                "a shared borrow resolved while the exclusive guard was held"
+            );
+        }
+
+        let guard = cell.borrow().await;
+        assert_eq!(*guard, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_parked_borrow_wakes_on_exclusive_release() {
+        let cell = RcRef::new(41);
+        let guard = cell.borrow_mut().await;
+
+        let cell2 = cell.clone();
+        let handle = tokio::spawn(async move {
+            let guard = cell2.borrow().await;
+            *guard + 1
+        });
+
+        // Give the spawned task a chance to poll `borrow()` and genuinely park
+        // behind the exclusive guard still held above, instead of racing to
+        // acquire it before we've even dropped `guard`.
+        tokio::task::yield_now().await;
+
+        drop(guard);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("parked borrow never woke up after the exclusive guard dropped")
+            .unwrap();
+
+        assert_eq!(result, 42);
+    }
+
+    #[tokio::test]
+    async fn test_exclusive_release_wakes_all_contiguous_shared_waiters() {
+        let cell = RcRef::new(0);
+        let guard = cell.borrow_mut().await;
+
+        let mut handles = Vec::new();
+        for _ in 0..3 {
+            let cell = cell.clone();
+            handles.push(tokio::spawn(async move {
+                let _guard = cell.borrow().await;
+            }));
+        }
+
+        // Let all three spawned tasks poll `borrow()` and park behind the
+        // exclusive guard still held above.
+        for _ in 0..3 {
+            tokio::task::yield_now().await;
+        }
+
+        drop(guard);
+
+        // A single release should wake every contiguous shared waiter at once,
+        // not serialize them one wake-up at a time.
+        for handle in handles {
+            tokio::time::timeout(Duration::from_secs(1), handle)
+                .await
+                .expect("a shared waiter parked behind the exclusive borrow never woke up")
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_try_unwrap_fails_with_outstanding_clone() {
+        let cell = RcRef::new(1);
+        let clone = cell.clone();
+
+        assert!(RcRef::try_unwrap(cell).is_err());
+        match RcRef::try_unwrap(clone) {
+            Ok(value) => assert_eq!(value, 1),
+            Err(_) => panic!("expected the last handle to unwrap"),
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
+use futures::stream::{self, Stream};
 
 #[async_trait]
 pub trait AsyncPaymentProcessor {
@@ -49,9 +50,31 @@ impl AsyncPaymentProcessor for JSONPayments {
         }
 }
 
+impl JSONPayments {
+    /// Turns this lending processor into an owned `futures::Stream<Item = PaymentInfo>`,
+    /// so callers can compose with `StreamExt` instead of calling `next_payment` by hand.
+    ///
+    /// Built with `stream::unfold`: the processor is moved into the seed and moved
+    /// into the async closure on each step, which sidesteps the lending design's
+    /// single-borrow limitation by yielding owned, parsed items.
+    pub fn into_stream(self) -> impl Stream<Item = PaymentInfo> {
+        stream::unfold(self, |mut processor| async move {
+            match processor.next_payment().await {
+                Some(row) => {
+                    let info: PaymentInfo =
+                        serde_json::from_str(row).expect("valid payment json");
+                    Some((info, processor))
+                }
+                None => None,
+            }
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use futures::StreamExt;
 
     #[tokio::test]
     async fn test_replication_works() {
@@ -74,4 +97,24 @@ mod tests {
 
         assert_eq!(second_processed, Some(50.0));
     }
+
+    #[tokio::test]
+    async fn test_into_stream_collects_owned_items() {
+        let processor = JSONPayments {
+            tx_list: vec![
+                r#"{"date":"2025-01-01","amount":100.0,"method":"credit","is_successful":true}"#.to_string(),
+                r#"{"date":"2025-01-02","amount":50.0,"method":"debit","is_successful":true}"#.to_string(),
+                r#"{"date":"2025-01-03","amount":75.0,"method":"credit","is_successful":false}"#.to_string(),
+            ],
+            position: 0,
+        };
+
+        let amounts: Vec<f64> = processor
+            .into_stream()
+            .map(|info| info.amount)
+            .collect()
+            .await;
+
+        assert_eq!(amounts, vec![100.0, 50.0, 75.0]);
+    }
 }
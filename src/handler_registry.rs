@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+
+use futures::{pin_mut, Stream, StreamExt};
+
+use crate::PaymentInfo;
+
+type Handler = Box<dyn Fn(PaymentInfo) -> Pin<Box<dyn Future<Output = ()> + Send>> + Send + Sync>;
+
+/// Routes payments to async handlers keyed by their `method` field (`"credit"`,
+/// `"debit"`, ...), turning the crate into a small event-routing layer rather than
+/// a single fixed transform.
+///
+/// Build one with [`HandlerRegistry::new`], register a handler per method with
+/// [`HandlerRegistry::register`], then hand it a stream of parsed payments (e.g.
+/// from [`PaymentsData::into_stream`](crate::PaymentsData::into_stream)) via
+/// [`HandlerRegistry::dispatch`].
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, Handler>,
+}
+
+impl HandlerRegistry {
+    pub fn new() -> Self {
+        Self {
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Registers an async handler to run for every payment whose `method` matches.
+    ///
+    /// The callback is boxed as `move |p| Box::pin(cb(p))` so it's owned for
+    /// `'static` rather than borrowing `cb`, which is what lets the registry hold
+    /// it past the call to `register`.
+    pub fn register<C, F>(&mut self, method: &str, cb: C)
+    where
+        C: Fn(PaymentInfo) -> F + Send + Sync + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.handlers
+            .insert(method.to_string(), Box::new(move |p| Box::pin(cb(p))));
+    }
+
+    /// Drains `payments`, awaiting the handler registered for each one's `method`.
+    ///
+    /// Payments whose method has no registered handler are silently dropped.
+    pub async fn dispatch(&self, payments: impl Stream<Item = PaymentInfo>) {
+        pin_mut!(payments);
+        while let Some(payment) = payments.next().await {
+            if let Some(handler) = self.handlers.get(payment.method()) {
+                handler(payment).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn sample_payments() -> Vec<PaymentInfo> {
+        vec![
+            serde_json::from_str(
+                r#"{"date":"2025-01-01","amount":100.0,"method":"credit","is_successful":true}"#,
+            )
+            .unwrap(),
+            serde_json::from_str(
+                r#"{"date":"2025-01-02","amount":50.0,"method":"debit","is_successful":true}"#,
+            )
+            .unwrap(),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_routes_by_method() {
+        let credit_seen: Arc<Mutex<Vec<PaymentInfo>>> = Arc::new(Mutex::new(Vec::new()));
+        let debit_seen: Arc<Mutex<Vec<PaymentInfo>>> = Arc::new(Mutex::new(Vec::new()));
+
+        let mut registry = HandlerRegistry::new();
+        {
+            let credit_seen = credit_seen.clone();
+            registry.register("credit", move |p: PaymentInfo| {
+                let credit_seen = credit_seen.clone();
+                async move {
+                    credit_seen.lock().unwrap().push(p);
+                }
+            });
+        }
+        {
+            let debit_seen = debit_seen.clone();
+            registry.register("debit", move |p: PaymentInfo| {
+                let debit_seen = debit_seen.clone();
+                async move {
+                    debit_seen.lock().unwrap().push(p);
+                }
+            });
+        }
+
+        let payments = sample_payments();
+        registry.dispatch(futures::stream::iter(payments.clone())).await;
+
+        assert_eq!(*credit_seen.lock().unwrap(), vec![payments[0].clone()]);
+        assert_eq!(*debit_seen.lock().unwrap(), vec![payments[1].clone()]);
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_ignores_unregistered_methods() {
+        let registry = HandlerRegistry::new();
+
+        // No handlers registered, so draining the payments should just not panic.
+        registry.dispatch(futures::stream::iter(sample_payments())).await;
+    }
+}
@@ -1,22 +1,116 @@
+use std::future::Future;
+use std::pin::Pin;
+
 use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::stream::{self, FuturesOrdered, Stream};
+use futures::StreamExt;
 use serde::{Serialize, Deserialize};
 
+pub mod async_ref_cell;
 pub mod day1_replication;
+pub mod handler_registry;
+
+use async_ref_cell::RcRef;
 
 #[async_trait]
 pub trait AsyncPaymentProcessor {
     type Payment<'a> where Self: 'a;
-    
+
     async fn next_payment(&mut self) -> Option<Self::Payment<'_>>;
-    
+
     // Transform method - what should this do?
     async fn process<F, T>(&mut self, f: F) -> Option<T>
-    where 
+    where
         F: Fn(Self::Payment<'_>) -> T + Send,
         T: Send;
+
+    /// Like [`process`](Self::process), but awaits an async transform instead of a
+    /// synchronous one, so callers can validate a payment against a remote gateway,
+    /// write it to a DB, etc. before producing a result.
+    ///
+    /// `f` returns a boxed future rather than `impl Future` because the future
+    /// needs to borrow the yielded payment for the duration of the `.await`;
+    /// boxing is what lets that borrow (`'s` below) outlive the call to `f`
+    /// without requiring the future itself to be `'static`. The borrow is tied
+    /// to `&'s mut self` rather than a higher-ranked `for<'t>` bound, since a GAT
+    /// input type with an HRTB `Fn` bound over an associated-type `Output` isn't
+    /// well-formed (`Output` would reference a lifetime absent from the trait's
+    /// own input types).
+    async fn process_async<'s, F, T>(&'s mut self, f: F) -> Option<T>
+    where
+        F: FnOnce(Self::Payment<'s>) -> Pin<Box<dyn Future<Output = T> + Send + 's>> + Send,
+        T: Send;
 }
 
-#[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// Runs the async transform `f` over `rows`, up to `concurrency` at a time, and
+/// returns the results in the same order as `rows`.
+///
+/// The rows are owned `String`s rather than borrowed slices, so each submitted
+/// future is `'static` and can be pushed onto a [`FuturesOrdered`] without
+/// borrowing from a shared buffer — that's what avoids the "may outlive the
+/// current function" lifetime errors a borrowing version would hit.
+async fn process_rows_concurrently<F, Fut, T>(rows: Vec<String>, concurrency: usize, f: F) -> Vec<T>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let mut in_flight: FuturesOrdered<BoxFuture<'static, T>> = FuturesOrdered::new();
+    let mut results = Vec::with_capacity(rows.len());
+
+    for row in rows {
+        if in_flight.len() >= concurrency {
+            if let Some(result) = in_flight.next().await {
+                results.push(result);
+            }
+        }
+        in_flight.push_back(Box::pin(f(row)));
+    }
+
+    while let Some(result) = in_flight.next().await {
+        results.push(result);
+    }
+
+    results
+}
+
+/// Distributes owned `rows` across up to `workers` spawned tasks (one per chunk of
+/// `rows.len() / workers` rows, rounded up) that each parse their
+/// share with `parse` and push the result into one shared [`RcRef<Vec<PaymentInfo>>`]
+/// sink, then returns the sink's contents once every worker has finished.
+///
+/// The sink is what lets several concurrent tasks accumulate into the same `Vec`
+/// despite the lending processors' single-borrow rule: each worker holds its own
+/// `RcRef` handle and awaits `borrow_mut()` before pushing.
+async fn fan_out_rows<P>(rows: Vec<String>, workers: usize, parse: P) -> Vec<PaymentInfo>
+where
+    P: Fn(&str) -> PaymentInfo + Copy + Send + 'static,
+{
+    let workers = workers.max(1);
+    let sink: RcRef<Vec<PaymentInfo>> = RcRef::new(Vec::new());
+    let chunk_size = rows.len().div_ceil(workers).max(1);
+
+    let mut handles = Vec::new();
+    for chunk in rows.chunks(chunk_size) {
+        let chunk = chunk.to_vec();
+        let sink = sink.clone();
+        handles.push(tokio::spawn(async move {
+            for row in chunk {
+                let info = parse(&row);
+                sink.borrow_mut().await.push(info);
+            }
+        }));
+    }
+
+    for handle in handles {
+        handle.await.expect("fan_out worker panicked");
+    }
+
+    RcRef::try_unwrap(sink).unwrap_or_else(|_| panic!("sink still has outstanding handles"))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaymentInfo {
     date: String,
     amount: f64,
@@ -24,6 +118,12 @@ pub struct PaymentInfo {
     is_successful: bool,
 }
 
+impl PaymentInfo {
+    pub(crate) fn method(&self) -> &str {
+        &self.method
+    }
+}
+
 /// `PaymentsData` processes a list of payment JSON strings.
 /// 
 /// # GAT Justification
@@ -85,16 +185,86 @@ impl AsyncPaymentProcessor for PaymentsData {
     }
 
     async fn process<F, T>(&mut self, f: F) -> Option<T>
-    where 
+    where
         F: Fn(Self::Payment<'_>) -> T + Send,
         T: Send,
     {
         self.next_payment().await.map(f)
     }
+
+    async fn process_async<'s, F, T>(&'s mut self, f: F) -> Option<T>
+    where
+        F: FnOnce(Self::Payment<'s>) -> Pin<Box<dyn Future<Output = T> + Send + 's>> + Send,
+        T: Send,
+    {
+        match self.next_payment().await {
+            Some(row) => Some(f(row).await),
+            None => None,
+        }
+    }
+}
+
+impl PaymentsData {
+    /// Turns this lending processor into an owned `futures::Stream<Item = PaymentInfo>`.
+    ///
+    /// The lending design forbids holding more than one borrowed `&str` at a time,
+    /// so the stream can't yield borrowed rows. Instead each row is parsed into an
+    /// owned `PaymentInfo` before it's handed to the caller, which is what lets the
+    /// result be composed with `StreamExt` (`map`, `filter`, `take`, `collect`, ...).
+    ///
+    /// Built with `stream::unfold`: the processor is moved into the seed, and each
+    /// step moves it into the async closure, awaits `next_payment`, and threads the
+    /// processor back out alongside the parsed item.
+    pub fn into_stream(self) -> impl Stream<Item = PaymentInfo> {
+        stream::unfold(self, |mut processor| async move {
+            match processor.next_payment().await {
+                Some(row) => {
+                    let info: PaymentInfo =
+                        serde_json::from_str(row).expect("valid payment json");
+                    Some((info, processor))
+                }
+                None => None,
+            }
+        })
+    }
+
+    /// Drains the processor and runs `f` over every row, up to `concurrency` rows
+    /// at once, preserving input order in the returned `Vec`.
+    ///
+    /// Rows are cloned into owned `String`s up front: the lending design means
+    /// overlapping futures can't each hold a `&str` borrow into the same buffer,
+    /// so the futures submitted to the underlying `FuturesOrdered` must own their
+    /// row instead.
+    pub async fn process_all<F, Fut, T>(&mut self, concurrency: usize, f: F) -> Vec<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next_payment().await {
+            rows.push(row.to_string());
+        }
+        process_rows_concurrently(rows, concurrency, f).await
+    }
+
+    /// Drains the processor and spawns up to `workers` tasks that parse rows into
+    /// `PaymentInfo` and accumulate them into one shared sink, returning the
+    /// combined results. See [`fan_out_rows`] for how the sink is shared safely.
+    pub async fn fan_out(mut self, workers: usize) -> Vec<PaymentInfo> {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next_payment().await {
+            rows.push(row.to_string());
+        }
+        fan_out_rows(rows, workers, |row| {
+            serde_json::from_str(row).expect("valid payment json")
+        })
+        .await
+    }
 }
 
 /// `CsvPaymentsData` processes a list of payment CSV rows.
-/// 
+///
 /// # GAT Justification
 /// 
 /// This type requires GATs because it yields borrowed `&str` references to payment 
@@ -157,18 +327,87 @@ impl AsyncPaymentProcessor for CsvPaymentsData {
     }
 
     async fn process<F, T>(&mut self, f: F) -> Option<T>
-    where 
+    where
         F: Fn(Self::Payment<'_>) -> T + Send,
         T: Send,
     {
         self.next_payment().await.map(f)
     }
+
+    async fn process_async<'s, F, T>(&'s mut self, f: F) -> Option<T>
+    where
+        F: FnOnce(Self::Payment<'s>) -> Pin<Box<dyn Future<Output = T> + Send + 's>> + Send,
+        T: Send,
+    {
+        match self.next_payment().await {
+            Some(row) => Some(f(row).await),
+            None => None,
+        }
+    }
+}
+
+impl CsvPaymentsData {
+    /// Turns this lending processor into an owned `futures::Stream<Item = PaymentInfo>`.
+    ///
+    /// See [`PaymentsData::into_stream`] for why the stream yields owned items
+    /// rather than borrowed `&str` rows.
+    #[allow(clippy::manual_map)]
+    pub fn into_stream(self) -> impl Stream<Item = PaymentInfo> {
+        stream::unfold(self, |mut processor| async move {
+            match processor.next_payment().await {
+                Some(row) => Some((parse_csv_row(row), processor)),
+                None => None,
+            }
+        })
+    }
+
+    /// Drains the processor and runs `f` over every row, up to `concurrency` rows
+    /// at once, preserving input order in the returned `Vec`.
+    ///
+    /// See [`PaymentsData::process_all`] for why the rows are cloned up front.
+    pub async fn process_all<F, Fut, T>(&mut self, concurrency: usize, f: F) -> Vec<T>
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next_payment().await {
+            rows.push(row.to_string());
+        }
+        process_rows_concurrently(rows, concurrency, f).await
+    }
+
+    /// Drains the processor and spawns up to `workers` tasks that parse rows into
+    /// `PaymentInfo` and accumulate them into one shared sink, returning the
+    /// combined results. See [`fan_out_rows`] for how the sink is shared safely.
+    pub async fn fan_out(mut self, workers: usize) -> Vec<PaymentInfo> {
+        let mut rows = Vec::new();
+        while let Some(row) = self.next_payment().await {
+            rows.push(row.to_string());
+        }
+        fan_out_rows(rows, workers, parse_csv_row).await
+    }
+}
+
+/// Parses a comma-separated row with the columns `date,amount,method,is_successful`
+/// into a `PaymentInfo`, stripping any double quotes at either end of each column
+/// (the tests below use rows like `2025-01-01","100.0","credit","true`).
+fn parse_csv_row(row: &str) -> PaymentInfo {
+    let cols: Vec<&str> = row.split(',').collect();
+    PaymentInfo {
+        date: cols[0].trim_matches('"').to_string(),
+        amount: cols[1].trim_matches('"').parse().expect("valid amount"),
+        method: cols[2].trim_matches('"').to_string(),
+        is_successful: cols[3].trim_matches('"').parse().expect("valid bool"),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use futures::StreamExt;
+
     // Helper function idea - or inline the data?
     fn sample_payments() -> Vec<String> {
         vec![
@@ -230,6 +469,101 @@ mod tests {
         assert_eq!(amount, Some(100.0));
     }
 
+    #[tokio::test]
+    async fn test_process_async_awaits_transform() {
+        let json_payments = sample_payments();
+        let mut processor = PaymentsData {
+            tx_list: json_payments,
+            position: 0,
+        };
+
+        let amount = processor
+            .process_async(|json: &str| -> Pin<Box<dyn Future<Output = f64> + Send + '_>> {
+                Box::pin(async move {
+                    let info: PaymentInfo = serde_json::from_str(json).unwrap();
+                    info.amount
+                })
+            })
+            .await;
+
+        assert_eq!(amount, Some(100.0));
+    }
+
+    #[tokio::test]
+    async fn test_process_all_preserves_order() {
+        let mut processor = PaymentsData {
+            tx_list: sample_payments(),
+            position: 0,
+        };
+
+        let amounts = processor
+            .process_all(2, |json: String| async move {
+                let info: PaymentInfo = serde_json::from_str(&json).unwrap();
+                info.amount
+            })
+            .await;
+
+        assert_eq!(amounts, vec![100.0, 50.0, 75.0]);
+    }
+
+    #[tokio::test]
+    async fn test_process_all_respects_concurrency_bound() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let rows: Vec<String> = (0..6)
+            .map(|i| {
+                format!(
+                    r#"{{"date":"2025-01-01","amount":{i}.0,"method":"credit","is_successful":true}}"#
+                )
+            })
+            .collect();
+        let mut processor = PaymentsData {
+            tx_list: rows,
+            position: 0,
+        };
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let concurrency = 2;
+
+        processor
+            .process_all(concurrency, {
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                move |_json: String| {
+                    let in_flight = in_flight.clone();
+                    let max_in_flight = max_in_flight.clone();
+                    async move {
+                        let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_in_flight.fetch_max(current, Ordering::SeqCst);
+                        tokio::task::yield_now().await;
+                        in_flight.fetch_sub(1, Ordering::SeqCst);
+                    }
+                }
+            })
+            .await;
+
+        assert!(max_in_flight.load(Ordering::SeqCst) <= concurrency);
+        assert!(
+            max_in_flight.load(Ordering::SeqCst) >= 2,
+            "expected futures to genuinely overlap up to the concurrency bound"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_collects_all_payments() {
+        let processor = PaymentsData {
+            tx_list: sample_payments(),
+            position: 0,
+        };
+
+        let mut amounts: Vec<f64> = processor.fan_out(2).await.iter().map(|p| p.amount).collect();
+        amounts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(amounts, vec![50.0, 75.0, 100.0]);
+    }
+
     #[tokio::test]
     async fn test_empty_data() {
         let mut processor = PaymentsData {
@@ -291,5 +625,38 @@ mod tests {
         assert_ne!(payment1, payment2);
         */
     }
+
+    #[tokio::test]
+    async fn test_json_into_stream_collects_owned_items() {
+        let processor = PaymentsData {
+            tx_list: sample_payments(),
+            position: 0,
+        };
+
+        let amounts: Vec<f64> = processor
+            .into_stream()
+            .map(|info| info.amount)
+            .collect()
+            .await;
+
+        assert_eq!(amounts, vec![100.0, 50.0, 75.0]);
+    }
+
+    #[tokio::test]
+    async fn test_csv_into_stream_collects_owned_items() {
+        let csv_processor = CsvPaymentsData {
+            rows: vec![
+                r#"2025-01-01","100.0","credit","true"#.to_string(),
+                r#"2025-01-02","150.0","debit","false"#.to_string(),
+            ],
+            position: 0,
+        };
+
+        let payments: Vec<PaymentInfo> = csv_processor.into_stream().collect().await;
+
+        assert_eq!(payments.len(), 2);
+        assert_eq!(payments[0].amount, 100.0);
+        assert_eq!(payments[1].method, "debit");
+    }
 }
 